@@ -1,4 +1,8 @@
+use std::collections::HashMap;
+
+use crate::format::{FromFields, ToFields};
 use crate::projector::{Project, Projector};
+use crate::timestamp::Timestamp;
 
 #[derive(Clone)]
 pub struct EventData {
@@ -20,17 +24,51 @@ pub struct VisitReport {
     pub visit_events: Vec<VisitEvent>,
 }
 
+impl ToFields for VisitEvent {
+    fn to_fields(&self) -> Vec<(&'static str, String)> {
+        let (kind, data) = match self {
+            VisitEvent::CheckIn(data) => ("check_in", data),
+            VisitEvent::CheckOut(data) => ("check_out", data),
+        };
+        vec![
+            ("type", kind.to_owned()),
+            ("id", data.id.clone()),
+            ("event_type", data.event_type.clone()),
+            ("timestamp", data.timestamp.clone()),
+            ("care_recipient_id", data.care_recipient_id.clone()),
+            ("caregiver_id", data.caregiver_id.clone()),
+        ]
+    }
+}
+
+impl FromFields for VisitEvent {
+    fn from_fields(fields: &HashMap<String, String>) -> Option<Self> {
+        let data = EventData {
+            id: fields.get("id")?.clone(),
+            event_type: fields.get("event_type")?.clone(),
+            timestamp: fields.get("timestamp")?.clone(),
+            care_recipient_id: fields.get("care_recipient_id")?.clone(),
+            caregiver_id: fields.get("caregiver_id")?.clone(),
+        };
+        match fields.get("type")?.as_str() {
+            "check_in" => Some(VisitEvent::CheckIn(data)),
+            "check_out" => Some(VisitEvent::CheckOut(data)),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Default, Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Period {
-    pub start: Option<String>,
-    pub end: Option<String>,
+    pub start: Option<Timestamp>,
+    pub end: Option<Timestamp>,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
 pub struct Participant {
     pub id: String,
-    pub start: Option<String>,
-    pub end: Option<String>,
+    pub start: Option<Timestamp>,
+    pub end: Option<Timestamp>,
 }
 
 #[derive(Default, Clone, Debug, Eq, PartialEq, Hash)]
@@ -39,19 +77,49 @@ pub struct Encounter {
     pub participant: Vec<Participant>,
 }
 
-fn min_opt(t0: &Option<String>, t1: &String) -> String {
-    if let Some(unw0) = t0 {
-        String::min(unw0.clone(), t1.clone())
-    } else {
-        t1.clone()
+impl ToFields for Encounter {
+    /// A lossy, flat summary: the period bounds plus a `;`-joined list of
+    /// participant ids. Enough for the codecs in `format` to export an
+    /// `Encounter`, though it does not round-trip back into one.
+    fn to_fields(&self) -> Vec<(&'static str, String)> {
+        vec![
+            (
+                "period_start",
+                self.period
+                    .start
+                    .map(|ts| ts.to_rfc3339())
+                    .unwrap_or_default(),
+            ),
+            (
+                "period_end",
+                self.period
+                    .end
+                    .map(|ts| ts.to_rfc3339())
+                    .unwrap_or_default(),
+            ),
+            (
+                "participant_ids",
+                self.participant
+                    .iter()
+                    .map(|p| p.id.clone())
+                    .collect::<Vec<_>>()
+                    .join(";"),
+            ),
+        ]
+    }
+}
+
+fn min_opt(t0: &Option<Timestamp>, t1: &Timestamp) -> Timestamp {
+    match t0 {
+        Some(unw0) => Timestamp::min(*unw0, *t1),
+        None => *t1,
     }
 }
 
-fn max_opt(t0: &Option<String>, t1: &String) -> String {
-    if let Some(unw0) = t0 {
-        String::max(unw0.clone(), t1.clone())
-    } else {
-        t1.clone()
+fn max_opt(t0: &Option<Timestamp>, t1: &Timestamp) -> Timestamp {
+    match t0 {
+        Some(unw0) => Timestamp::max(*unw0, *t1),
+        None => *t1,
     }
 }
 
@@ -60,31 +128,31 @@ fn apply_participant(event: &VisitEvent, participant: &Vec<Participant>) -> Vec<
         VisitEvent::CheckIn(c) => &c.caregiver_id,
         VisitEvent::CheckOut(c) => &c.caregiver_id,
     };
+    let raw_timestamp = match &event {
+        VisitEvent::CheckIn(c) => &c.timestamp,
+        VisitEvent::CheckOut(c) => &c.timestamp,
+    };
+    let Some(timestamp) = Timestamp::parse(raw_timestamp) else {
+        return participant.clone();
+    };
 
     let new_entry = match participant.into_iter().find(|p| p.id == *id) {
         Some(p) => match &event {
-            VisitEvent::CheckIn(c) => Participant {
+            VisitEvent::CheckIn(_) => Participant {
                 id: id.clone(),
-                start: Some(min_opt(&p.start, &c.timestamp)),
-                end: p.end.clone(),
+                start: Some(min_opt(&p.start, &timestamp)),
+                end: p.end,
             },
-            VisitEvent::CheckOut(c) => Participant {
+            VisitEvent::CheckOut(_) => Participant {
                 id: id.clone(),
-                start: p.start.clone(),
-                end: Some(max_opt(&p.end, &c.timestamp)),
+                start: p.start,
+                end: Some(max_opt(&p.end, &timestamp)),
             },
         },
-        None => match &event {
-            VisitEvent::CheckIn(c) => Participant {
-                id: id.clone(),
-                start: Some(c.timestamp.clone()),
-                end: None,
-            },
-            VisitEvent::CheckOut(c) => Participant {
-                id: id.clone(),
-                start: Some(c.timestamp.clone()),
-                end: None,
-            },
+        None => Participant {
+            id: id.clone(),
+            start: Some(timestamp),
+            end: None,
         },
     };
 
@@ -97,14 +165,22 @@ fn apply_participant(event: &VisitEvent, participant: &Vec<Participant>) -> Vec<
 }
 
 fn apply_period(event: &VisitEvent, existing: &Period) -> Period {
+    let raw_timestamp = match event {
+        VisitEvent::CheckIn(c) => &c.timestamp,
+        VisitEvent::CheckOut(c) => &c.timestamp,
+    };
+    let Some(timestamp) = Timestamp::parse(raw_timestamp) else {
+        return existing.clone();
+    };
+
     match event {
-        VisitEvent::CheckIn(c) => Period {
-            start: Some(min_opt(&existing.start, &c.timestamp)),
-            end: existing.end.clone(),
+        VisitEvent::CheckIn(_) => Period {
+            start: Some(min_opt(&existing.start, &timestamp)),
+            end: existing.end,
         },
-        VisitEvent::CheckOut(c) => Period {
-            start: existing.start.clone(),
-            end: Some(max_opt(&existing.end, &c.timestamp)),
+        VisitEvent::CheckOut(_) => Period {
+            start: existing.start,
+            end: Some(max_opt(&existing.end, &timestamp)),
         },
     }
 }
@@ -121,6 +197,31 @@ pub fn encounter_projector() -> Projector<'static, VisitReport, Encounter> {
     })
 }
 
+fn visit_event_applier(encounter: &Encounter, event: &VisitEvent) -> Encounter {
+    Encounter {
+        period: apply_period(event, &encounter.period),
+        participant: apply_participant(event, &encounter.participant),
+    }
+}
+
+fn care_recipient_id(event: &VisitEvent) -> &String {
+    match event {
+        VisitEvent::CheckIn(c) => &c.care_recipient_id,
+        VisitEvent::CheckOut(c) => &c.care_recipient_id,
+    }
+}
+
+/// Folds every `VisitReport` in `reports` into one `Encounter` per
+/// `care_recipient_id`, rather than collapsing all care recipients into a
+/// single aggregate the way `encounter_projector` does.
+pub fn encounters_by_care_recipient<S: Iterator<Item = VisitReport>>(
+    reports: S,
+) -> HashMap<String, Encounter> {
+    let events = reports.flat_map(|report| report.visit_events.into_iter());
+    Projector::from_applier(&visit_event_applier)
+        .project_final_states_by_key(events, |event| care_recipient_id(event).clone())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -161,8 +262,8 @@ mod tests {
         assert_eq!(
             result.period,
             Period {
-                start: Some("2020-12-31T00:00:00.001Z".to_owned()),
-                end: Some("2021-01-01T10:00:00.000Z".to_owned()),
+                start: Timestamp::parse("2020-12-31T00:00:00.001Z"),
+                end: Timestamp::parse("2021-01-01T10:00:00.000Z"),
             },
         );
         assert_eq!(
@@ -170,15 +271,97 @@ mod tests {
             vec![
                 Participant {
                     id: "0".to_owned(),
-                    start: Some("2021-01-01T00:00:00.000Z".to_owned()),
-                    end: Some("2021-01-01T10:00:00.000Z".to_owned()),
+                    start: Timestamp::parse("2021-01-01T00:00:00.000Z"),
+                    end: Timestamp::parse("2021-01-01T10:00:00.000Z"),
                 },
                 Participant {
                     id: "1".to_owned(),
-                    start: Some("2020-12-31T00:00:00.001Z".to_owned()),
+                    start: Timestamp::parse("2020-12-31T00:00:00.001Z"),
                     end: None,
                 }
             ],
         );
     }
+
+    #[test]
+    fn by_care_recipient_keeps_recipients_separate() {
+        let report = VisitReport {
+            id: "1".to_owned(),
+            visit_events: vec![
+                VisitEvent::CheckIn(EventData {
+                    event_type: "check_in".to_owned(),
+                    id: "0".to_owned(),
+                    care_recipient_id: "recipient-0".to_owned(),
+                    caregiver_id: "0".to_owned(),
+                    timestamp: "2021-01-01T00:00:00.000Z".to_owned(),
+                }),
+                VisitEvent::CheckOut(EventData {
+                    event_type: "check_out".to_owned(),
+                    id: "0".to_owned(),
+                    care_recipient_id: "recipient-0".to_owned(),
+                    caregiver_id: "0".to_owned(),
+                    timestamp: "2021-01-01T10:00:00.000Z".to_owned(),
+                }),
+                VisitEvent::CheckIn(EventData {
+                    event_type: "check_in".to_owned(),
+                    id: "1".to_owned(),
+                    care_recipient_id: "recipient-1".to_owned(),
+                    caregiver_id: "1".to_owned(),
+                    timestamp: "2021-02-01T00:00:00.000Z".to_owned(),
+                }),
+            ],
+        };
+
+        let result = encounters_by_care_recipient(vec![report].into_iter());
+
+        assert_eq!(
+            result.get("recipient-0").map(|e| &e.period),
+            Some(&Period {
+                start: Timestamp::parse("2021-01-01T00:00:00.000Z"),
+                end: Timestamp::parse("2021-01-01T10:00:00.000Z"),
+            })
+        );
+        assert_eq!(
+            result.get("recipient-1").map(|e| &e.period),
+            Some(&Period {
+                start: Timestamp::parse("2021-02-01T00:00:00.000Z"),
+                end: None,
+            })
+        );
+    }
+
+    #[test]
+    fn period_is_chronologically_correct_across_timezone_offsets() {
+        let report = VisitReport {
+            id: "1".to_owned(),
+            visit_events: vec![
+                VisitEvent::CheckIn(EventData {
+                    event_type: "check_in".to_owned(),
+                    id: "0".to_owned(),
+                    care_recipient_id: "0".to_owned(),
+                    caregiver_id: "0".to_owned(),
+                    // 21:00 Zulu, lexically greater than the check-out below
+                    // despite being the earlier instant.
+                    timestamp: "2021-01-01T23:00:00+02:00".to_owned(),
+                }),
+                VisitEvent::CheckOut(EventData {
+                    event_type: "check_out".to_owned(),
+                    id: "0".to_owned(),
+                    care_recipient_id: "0".to_owned(),
+                    caregiver_id: "0".to_owned(),
+                    timestamp: "2021-01-01T22:00:00Z".to_owned(),
+                }),
+            ],
+        };
+
+        let result = encounter_projector().from_stream(vec![report].into_iter());
+
+        assert_eq!(
+            result.period,
+            Period {
+                start: Timestamp::parse("2021-01-01T23:00:00+02:00"),
+                end: Timestamp::parse("2021-01-01T22:00:00Z"),
+            },
+        );
+    }
 }
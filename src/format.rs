@@ -0,0 +1,306 @@
+use std::collections::HashMap;
+use std::io::{BufRead, Write};
+
+/// Parse-time state a `Decoder` carries across lines. The built-in decoders
+/// record the raw `timestamp` field of the most recent row in
+/// `base_timestamp`; they do not resolve later rows' timestamps against it.
+#[derive(Default, Clone)]
+pub struct Context {
+    pub base_timestamp: Option<String>,
+}
+
+/// A record that can be flattened to an ordered list of named string
+/// columns — the common denominator every codec in this module writes.
+pub trait ToFields {
+    fn to_fields(&self) -> Vec<(&'static str, String)>;
+}
+
+/// The inverse of `ToFields`: rebuilds a record from its named columns.
+pub trait FromFields: Sized {
+    fn from_fields(fields: &HashMap<String, String>) -> Option<Self>;
+}
+
+/// Reads a serialized event stream.
+///
+/// Deliberately object-safe (no generic method parameters) so a caller can
+/// pick a format by name at runtime, e.g. via `decoder_by_name`.
+pub trait Decoder<TEvent> {
+    fn events<'a>(
+        &self,
+        reader: &'a mut dyn BufRead,
+        ctx: &'a mut Context,
+    ) -> Box<dyn Iterator<Item = Result<TEvent, String>> + 'a>;
+}
+
+/// Writes a projected entity stream. Object-safe for the same reason as
+/// `Decoder`.
+pub trait Encoder<TEntity> {
+    fn write(
+        &self,
+        entities: &mut dyn Iterator<Item = TEntity>,
+        writer: &mut dyn Write,
+    ) -> std::io::Result<()>;
+}
+
+fn split_top_level(input: &str, delim: char) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut in_quotes = false;
+    let mut start = 0;
+    for (i, c) in input.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if c == delim && !in_quotes {
+            parts.push(&input[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    parts.push(&input[start..]);
+    parts
+}
+
+fn unquote(field: &str) -> Option<String> {
+    let field = field.trim();
+    let inner = field.strip_prefix('"')?.strip_suffix('"')?;
+    Some(inner.replace("\\\"", "\"").replace("\\\\", "\\"))
+}
+
+fn escape_json(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Newline-delimited JSON, one flat object per line.
+pub struct NdjsonCodec;
+
+impl NdjsonCodec {
+    fn parse_object(line: &str) -> Option<HashMap<String, String>> {
+        let inner = line.trim().strip_prefix('{')?.strip_suffix('}')?;
+        if inner.trim().is_empty() {
+            return Some(HashMap::new());
+        }
+        split_top_level(inner, ',')
+            .into_iter()
+            .map(|pair| {
+                let (key, value) = pair.split_once(':')?;
+                Some((unquote(key)?, unquote(value)?))
+            })
+            .collect()
+    }
+
+    fn to_line<T: ToFields>(value: &T) -> String {
+        let body = value
+            .to_fields()
+            .into_iter()
+            .map(|(k, v)| format!("\"{}\":\"{}\"", k, escape_json(&v)))
+            .collect::<Vec<_>>()
+            .join(",");
+        format!("{{{}}}", body)
+    }
+}
+
+impl<TEvent: FromFields> Decoder<TEvent> for NdjsonCodec {
+    fn events<'a>(
+        &self,
+        reader: &'a mut dyn BufRead,
+        ctx: &'a mut Context,
+    ) -> Box<dyn Iterator<Item = Result<TEvent, String>> + 'a> {
+        Box::new(reader.lines().filter_map(move |line| {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.to_string())),
+            };
+            if line.trim().is_empty() {
+                return None;
+            }
+            let fields = match Self::parse_object(&line) {
+                Some(fields) => fields,
+                None => return Some(Err(format!("invalid NDJSON line: {}", line))),
+            };
+            if let Some(timestamp) = fields.get("timestamp") {
+                ctx.base_timestamp = Some(timestamp.clone());
+            }
+            Some(
+                TEvent::from_fields(&fields)
+                    .ok_or_else(|| format!("could not build event from {:?}", fields)),
+            )
+        }))
+    }
+}
+
+impl<TEntity: ToFields> Encoder<TEntity> for NdjsonCodec {
+    fn write(
+        &self,
+        entities: &mut dyn Iterator<Item = TEntity>,
+        writer: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        for entity in entities {
+            writeln!(writer, "{}", Self::to_line(&entity))?;
+        }
+        Ok(())
+    }
+}
+
+/// Comma-separated values with a header row of column names.
+pub struct CsvCodec;
+
+impl CsvCodec {
+    fn split_line(line: &str) -> Vec<String> {
+        split_top_level(line, ',')
+            .into_iter()
+            .map(|field| {
+                let field = field.trim();
+                match field.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+                    Some(inner) => inner.replace("\"\"", "\""),
+                    None => field.to_string(),
+                }
+            })
+            .collect()
+    }
+
+    fn escape(value: &str) -> String {
+        if value.contains(',') || value.contains('"') {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.to_string()
+        }
+    }
+}
+
+impl<TEvent: FromFields> Decoder<TEvent> for CsvCodec {
+    fn events<'a>(
+        &self,
+        reader: &'a mut dyn BufRead,
+        ctx: &'a mut Context,
+    ) -> Box<dyn Iterator<Item = Result<TEvent, String>> + 'a> {
+        let mut lines = reader.lines();
+        let header = match lines.next() {
+            Some(Ok(line)) => Self::split_line(&line),
+            _ => Vec::new(),
+        };
+
+        Box::new(lines.filter_map(move |line| {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => return Some(Err(err.to_string())),
+            };
+            if line.trim().is_empty() {
+                return None;
+            }
+            let values = Self::split_line(&line);
+            let fields: HashMap<String, String> = header.iter().cloned().zip(values).collect();
+            if let Some(timestamp) = fields.get("timestamp") {
+                ctx.base_timestamp = Some(timestamp.clone());
+            }
+            Some(
+                TEvent::from_fields(&fields)
+                    .ok_or_else(|| format!("could not build event from {:?}", fields)),
+            )
+        }))
+    }
+}
+
+impl<TEntity: ToFields> Encoder<TEntity> for CsvCodec {
+    fn write(
+        &self,
+        entities: &mut dyn Iterator<Item = TEntity>,
+        writer: &mut dyn Write,
+    ) -> std::io::Result<()> {
+        let mut header_written = false;
+        for entity in entities {
+            let fields = entity.to_fields();
+            if !header_written {
+                let header: Vec<&str> = fields.iter().map(|(k, _)| *k).collect();
+                writeln!(writer, "{}", header.join(","))?;
+                header_written = true;
+            }
+            let row: Vec<String> = fields.iter().map(|(_, v)| Self::escape(v)).collect();
+            writeln!(writer, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/// Selects a `Decoder` by name, so new formats can be registered without
+/// callers matching on format names themselves.
+pub fn decoder_by_name<TEvent: FromFields + 'static>(name: &str) -> Option<Box<dyn Decoder<TEvent>>> {
+    match name {
+        "ndjson" | "json" => Some(Box::new(NdjsonCodec)),
+        "csv" => Some(Box::new(CsvCodec)),
+        _ => None,
+    }
+}
+
+/// Selects an `Encoder` by name, mirroring `decoder_by_name`.
+pub fn encoder_by_name<TEntity: ToFields + 'static>(name: &str) -> Option<Box<dyn Encoder<TEntity>>> {
+    match name {
+        "ndjson" | "json" => Some(Box::new(NdjsonCodec)),
+        "csv" => Some(Box::new(CsvCodec)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encounter::{encounters_by_care_recipient, VisitEvent, VisitReport};
+
+    #[test]
+    fn ndjson_decodes_and_encodes_round_trip() {
+        let line = NdjsonCodec::to_line(&VisitEvent::from_fields(
+            &[
+                ("type".to_owned(), "check_in".to_owned()),
+                ("id".to_owned(), "0".to_owned()),
+                ("event_type".to_owned(), "check_in".to_owned()),
+                ("timestamp".to_owned(), "2021-01-01T00:00:00Z".to_owned()),
+                ("care_recipient_id".to_owned(), "0".to_owned()),
+                ("caregiver_id".to_owned(), "0".to_owned()),
+            ]
+            .into_iter()
+            .collect(),
+        )
+        .unwrap());
+
+        let mut reader = line.as_bytes();
+        let mut ctx = Context::default();
+        let codec = NdjsonCodec;
+        let events: Vec<VisitEvent> = Decoder::<VisitEvent>::events(&codec, &mut reader, &mut ctx)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(events.len(), 1);
+        assert_eq!(NdjsonCodec::to_line(&events[0]), line);
+        assert_eq!(ctx.base_timestamp.as_deref(), Some("2021-01-01T00:00:00Z"));
+    }
+
+    #[test]
+    fn decoder_events_stream_entities_encoder_write_end_to_end() {
+        let ndjson = "\
+            {\"type\":\"check_in\",\"id\":\"0\",\"event_type\":\"check_in\",\"timestamp\":\"2021-01-01T00:00:00Z\",\"care_recipient_id\":\"0\",\"caregiver_id\":\"0\"}\n\
+            {\"type\":\"check_out\",\"id\":\"0\",\"event_type\":\"check_out\",\"timestamp\":\"2021-01-01T10:00:00Z\",\"care_recipient_id\":\"0\",\"caregiver_id\":\"0\"}\n";
+
+        let mut reader = ndjson.as_bytes();
+        let mut ctx = Context::default();
+        let decoder: Box<dyn Decoder<VisitEvent>> = decoder_by_name("ndjson").unwrap();
+        let events: Vec<VisitEvent> = decoder
+            .events(&mut reader, &mut ctx)
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        let report = VisitReport {
+            id: "0".to_owned(),
+            visit_events: events,
+        };
+        let entities: Vec<crate::encounter::Encounter> =
+            encounters_by_care_recipient(vec![report].into_iter())
+                .into_values()
+                .collect();
+
+        let encoder: Box<dyn Encoder<crate::encounter::Encounter>> =
+            encoder_by_name("csv").unwrap();
+        let mut out = Vec::new();
+        encoder.write(&mut entities.into_iter(), &mut out).unwrap();
+
+        let csv = String::from_utf8(out).unwrap();
+        assert!(csv.starts_with("period_start,period_end,participant_ids\n"));
+        assert!(csv.contains("2021-01-01T10:00:00Z"));
+    }
+}
@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::io::BufRead;
+
+use crate::encounter::{Encounter, EventData, VisitEvent, VisitReport};
+use crate::timestamp::Timestamp;
+
+/// Parameters attached to an iCalendar property line, e.g. the `CN=...` in
+/// `ATTENDEE;CN=Jane Doe:mailto:jane@example.com`.
+pub type Props = HashMap<String, String>;
+
+/// A `BEGIN`/`END`-delimited iCalendar object (`VCALENDAR`, `VEVENT`, ...).
+///
+/// Properties are kept in a `Vec` per name rather than overwritten, since a
+/// `VEVENT` typically carries one `ATTENDEE` line per participant.
+#[derive(Default, Debug, Clone)]
+pub struct IcsObject {
+    pub name: String,
+    pub properties: HashMap<String, Vec<(Props, String)>>,
+    pub children: Vec<IcsObject>,
+}
+
+impl IcsObject {
+    fn first(&self, name: &str) -> Option<&str> {
+        self.properties
+            .get(name)
+            .and_then(|values| values.first())
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn parse_line(line: &str) -> Option<(String, Props, String)> {
+    let (key_part, value) = line.split_once(':')?;
+    let mut segments = key_part.split(';');
+    let name = segments.next()?.trim().to_uppercase();
+    let mut props = Props::new();
+    for segment in segments {
+        if let Some((k, v)) = segment.split_once('=') {
+            props.insert(k.trim().to_uppercase(), v.trim().to_string());
+        }
+    }
+    Some((name, props, value.trim().to_string()))
+}
+
+/// Parses an `.ics` stream into the top-level objects it contains (usually
+/// a single `VCALENDAR` with one `VEVENT` child per encounter), tracking
+/// `BEGIN`/`END` to nest children under their parent object.
+pub fn parse_objects<R: BufRead>(reader: R) -> Vec<IcsObject> {
+    let mut stack: Vec<IcsObject> = Vec::new();
+    let mut roots = Vec::new();
+
+    for line in reader.lines().map_while(Result::ok) {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let Some((name, props, value)) = parse_line(line) else {
+            continue;
+        };
+
+        match name.as_str() {
+            "BEGIN" => stack.push(IcsObject {
+                name: value,
+                ..Default::default()
+            }),
+            "END" => {
+                if let Some(finished) = stack.pop() {
+                    match stack.last_mut() {
+                        Some(parent) => parent.children.push(finished),
+                        None => roots.push(finished),
+                    }
+                }
+            }
+            _ => {
+                if let Some(current) = stack.last_mut() {
+                    current
+                        .properties
+                        .entry(name)
+                        .or_default()
+                        .push((props, value));
+                }
+            }
+        }
+    }
+
+    roots
+}
+
+fn vevents(objects: &[IcsObject]) -> Vec<&IcsObject> {
+    objects
+        .iter()
+        .flat_map(|object| {
+            if object.name == "VEVENT" {
+                vec![object]
+            } else {
+                vevents(&object.children)
+            }
+        })
+        .collect()
+}
+
+/// Converts each `VEVENT` found in `objects` into a `VisitReport`, turning
+/// every attendee's participation window (`X-START`/`X-END`) into a
+/// `CheckIn`/`CheckOut` pair so the result can be folded by
+/// `encounter_projector`.
+pub fn to_visit_reports(objects: &[IcsObject]) -> Vec<VisitReport> {
+    vevents(objects)
+        .into_iter()
+        .map(|vevent| {
+            let uid = vevent.first("UID").unwrap_or_default().to_owned();
+
+            let visit_events = vevent
+                .properties
+                .get("ATTENDEE")
+                .into_iter()
+                .flatten()
+                .flat_map(|(props, value)| {
+                    let caregiver_id = props.get("CN").cloned().unwrap_or_else(|| value.clone());
+                    let mut events = Vec::new();
+
+                    if let Some(start) = props.get("X-START").and_then(|s| Timestamp::parse_ics_basic(s)) {
+                        events.push(VisitEvent::CheckIn(EventData {
+                            id: format!("{}-{}-in", uid, caregiver_id),
+                            event_type: "check_in".to_owned(),
+                            timestamp: start.to_rfc3339(),
+                            care_recipient_id: uid.clone(),
+                            caregiver_id: caregiver_id.clone(),
+                        }));
+                    }
+                    if let Some(end) = props.get("X-END").and_then(|s| Timestamp::parse_ics_basic(s)) {
+                        events.push(VisitEvent::CheckOut(EventData {
+                            id: format!("{}-{}-out", uid, caregiver_id),
+                            event_type: "check_out".to_owned(),
+                            timestamp: end.to_rfc3339(),
+                            care_recipient_id: uid.clone(),
+                            caregiver_id,
+                        }));
+                    }
+
+                    events
+                })
+                .collect();
+
+            VisitReport {
+                id: uid,
+                visit_events,
+            }
+        })
+        .collect()
+}
+
+/// Serializes a projected `Encounter` to a single `VEVENT` block, tagged
+/// with `uid` (the care recipient id, which `Encounter` itself does not
+/// carry) so the output round-trips back through `to_visit_reports`.
+pub fn encounter_to_vevent(encounter: &Encounter, uid: &str) -> String {
+    let mut lines = vec!["BEGIN:VEVENT".to_owned(), format!("UID:{}", uid)];
+
+    if let Some(start) = encounter.period.start {
+        lines.push(format!("DTSTART:{}", start.to_ics_basic()));
+    }
+    if let Some(end) = encounter.period.end {
+        lines.push(format!("DTEND:{}", end.to_ics_basic()));
+    }
+
+    for participant in &encounter.participant {
+        let mut attendee = format!("ATTENDEE;CN={}", participant.id);
+        if let Some(start) = participant.start {
+            attendee.push_str(&format!(";X-START={}", start.to_ics_basic()));
+        }
+        if let Some(end) = participant.end {
+            attendee.push_str(&format!(";X-END={}", end.to_ics_basic()));
+        }
+        attendee.push_str(&format!(":mailto:{}", participant.id));
+        lines.push(attendee);
+    }
+
+    lines.push("END:VEVENT".to_owned());
+    lines.join("\r\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encounter::{encounters_by_care_recipient, Participant, Period};
+
+    #[test]
+    fn parses_nested_objects_and_accumulates_repeated_properties() {
+        let ics = "BEGIN:VCALENDAR\r\n\
+                   BEGIN:VEVENT\r\n\
+                   UID:recipient-0\r\n\
+                   ATTENDEE;CN=alice:mailto:alice@example.com\r\n\
+                   ATTENDEE;CN=bob:mailto:bob@example.com\r\n\
+                   END:VEVENT\r\n\
+                   END:VCALENDAR\r\n";
+
+        let objects = parse_objects(ics.as_bytes());
+        assert_eq!(objects.len(), 1);
+        assert_eq!(objects[0].name, "VCALENDAR");
+        assert_eq!(objects[0].children.len(), 1);
+
+        let vevent = &objects[0].children[0];
+        assert_eq!(vevent.name, "VEVENT");
+        assert_eq!(vevent.properties.get("ATTENDEE").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn exports_and_reimports_round_trip_through_encounter_projector() {
+        let encounter = Encounter {
+            period: Period {
+                start: Timestamp::parse("2021-01-01T00:00:00Z"),
+                end: Timestamp::parse("2021-01-01T10:00:00Z"),
+            },
+            participant: vec![Participant {
+                id: "caregiver-0".to_owned(),
+                start: Timestamp::parse("2021-01-01T00:00:00Z"),
+                end: Timestamp::parse("2021-01-01T10:00:00Z"),
+            }],
+        };
+
+        let vevent = encounter_to_vevent(&encounter, "recipient-0");
+        let ics = format!("BEGIN:VCALENDAR\r\n{}\r\nEND:VCALENDAR\r\n", vevent);
+
+        let objects = parse_objects(ics.as_bytes());
+        let reports = to_visit_reports(&objects);
+        assert_eq!(reports.len(), 1);
+
+        let rebuilt = encounters_by_care_recipient(reports.into_iter());
+        assert_eq!(
+            rebuilt.get("recipient-0"),
+            Some(&encounter),
+        );
+    }
+}
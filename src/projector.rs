@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+use std::hash::Hash;
 use std::marker::PhantomData;
 
 pub struct Projector<'a, TEntity, TEvent, TApplier>
@@ -48,6 +50,143 @@ where
     ) -> Option<TEntity> {
         self.stream_entities(stream).find(|e| matcher(e)).to_owned()
     }
+
+    /// Folds a single stream into one entity per key, instead of collapsing
+    /// every event into the same aggregate. Each event is applied only to
+    /// the running state for the key it belongs to, mirroring the usual
+    /// event-sourcing "aggregate id" partitioning.
+    pub fn stream_entities_by_key<'b, TKey, TKeyFn>(
+        &'b self,
+        stream: impl Iterator<Item = TEvent> + 'b,
+        key_fn: TKeyFn,
+    ) -> impl Iterator<Item = (TKey, TEntity)> + 'b
+    where
+        TKey: Eq + Hash + Clone + 'b,
+        TKeyFn: Fn(&TEvent) -> TKey + 'b,
+    {
+        stream.scan(HashMap::<TKey, TEntity>::new(), move |states, event| {
+            let key = key_fn(&event);
+            let prior = states.entry(key.clone()).or_insert_with(TEntity::default);
+            *prior = (self.applier)(prior, &event);
+            Some((key, prior.clone()))
+        })
+    }
+
+    /// The final state for every key seen in `stream`, discarding the
+    /// intermediate states `stream_entities_by_key` yields along the way.
+    pub fn project_final_states_by_key<TKey, TKeyFn>(
+        &self,
+        stream: impl Iterator<Item = TEvent>,
+        key_fn: TKeyFn,
+    ) -> HashMap<TKey, TEntity>
+    where
+        TKey: Eq + Hash + Clone,
+        TKeyFn: Fn(&TEvent) -> TKey,
+    {
+        let mut finals = HashMap::new();
+        for (key, entity) in self.stream_entities_by_key(stream, key_fn) {
+            finals.insert(key, entity);
+        }
+        finals
+    }
+}
+
+type Unapplier<'a, TEntity, TEvent> = &'a dyn Fn(&TEntity, &TEvent) -> TEntity;
+
+/// A projector that retains the events it has folded so a caller can step
+/// the projected state backward as well as forward.
+///
+/// Every applied event is kept in `history` alongside the entity it produced,
+/// and `cursor` marks how many of those events are currently "live". Undoing
+/// simply moves `cursor` back without discarding the tail, so `rewind_to` can
+/// still reach it; applying a fresh event after an undo truncates that tail,
+/// mirroring a redo buffer that is abandoned as soon as new history is
+/// written.
+pub struct ReversibleProjector<'a, TEntity, TEvent, TApplier>
+where
+    TEntity: Default + Clone,
+    TEvent: Clone,
+    TApplier: Fn(&TEntity, &TEvent) -> TEntity,
+{
+    applier: TApplier,
+    unapplier: Option<Unapplier<'a, TEntity, TEvent>>,
+    history: Vec<(TEvent, TEntity)>,
+    cursor: usize,
+}
+
+impl<'a, TEntity, TEvent, TApplier> ReversibleProjector<'a, TEntity, TEvent, TApplier>
+where
+    TEntity: Default + Clone,
+    TEvent: Clone,
+    TApplier: Fn(&TEntity, &TEvent) -> TEntity,
+{
+    pub fn from_applier(applier: TApplier) -> Self {
+        ReversibleProjector {
+            applier,
+            unapplier: None,
+            history: Vec::new(),
+            cursor: 0,
+        }
+    }
+
+    /// Attaches an inverse of the applier so `undo_last` can step back in
+    /// constant time instead of replaying the retained history.
+    pub fn with_unapplier(mut self, unapplier: Unapplier<'a, TEntity, TEvent>) -> Self {
+        self.unapplier = Some(unapplier);
+        self
+    }
+
+    /// The entity after the last applied (and not yet undone) event.
+    pub fn state(&self) -> TEntity {
+        self.history
+            .get(self.cursor.wrapping_sub(1))
+            .map(|(_, entity)| entity.clone())
+            .unwrap_or_default()
+    }
+
+    fn replay(&self, count: usize) -> TEntity {
+        self.history
+            .iter()
+            .take(count)
+            .fold(TEntity::default(), |state, (event, _)| {
+                (self.applier)(&state, event)
+            })
+    }
+
+    /// Applies `event` to the current state. If the cursor is behind the end
+    /// of `history` (because of a prior `undo_last`/`rewind_to`), the
+    /// abandoned redo tail is discarded first.
+    pub fn apply(&mut self, event: TEvent) -> TEntity {
+        self.history.truncate(self.cursor);
+        let next = (self.applier)(&self.state(), &event);
+        self.history.push((event, next.clone()));
+        self.cursor += 1;
+        next
+    }
+
+    /// Steps back one event, returning the recovered entity, or `None` if
+    /// there is nothing left to undo.
+    pub fn undo_last(&mut self) -> Option<TEntity> {
+        if self.cursor == 0 {
+            return None;
+        }
+        let recovered = match self.unapplier {
+            Some(unapplier) => {
+                let (event, entity) = &self.history[self.cursor - 1];
+                unapplier(entity, event)
+            }
+            None => self.replay(self.cursor - 1),
+        };
+        self.cursor -= 1;
+        Some(recovered)
+    }
+
+    /// Moves the cursor to the state after the first `n` events and returns
+    /// it. `n` is clamped to the length of the retained history.
+    pub fn rewind_to(&mut self, n: usize) -> TEntity {
+        self.cursor = n.min(self.history.len());
+        self.state()
+    }
 }
 
 #[cfg(test)]
@@ -61,6 +200,7 @@ mod tests {
         timestamp: Option<String>,
     }
 
+    #[derive(Clone)]
     struct TestEvent {
         id: String,
         timestamp: String,
@@ -235,4 +375,179 @@ mod tests {
             ],
         );
     }
+
+    fn test_unapplier(entity: &TestEntity, event: &TestEvent) -> TestEntity {
+        TestEntity {
+            id: entity.id.clone(),
+            timestamp: if entity.timestamp.as_deref() == Some(event.timestamp.as_str()) {
+                None
+            } else {
+                entity.timestamp.clone()
+            },
+        }
+    }
+
+    #[test]
+    fn undo_last_without_unapplier_replays_history() {
+        let mut projector = ReversibleProjector::from_applier(&test_applier);
+        projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-1"),
+        });
+        projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-8"),
+        });
+
+        let recovered = projector.undo_last();
+        assert_eq!(
+            recovered,
+            Some(TestEntity {
+                id: Some(String::from("id-1")),
+                timestamp: Some(String::from("ts-1")),
+            })
+        );
+        assert_eq!(projector.undo_last(), Some(TestEntity::default()));
+        assert_eq!(projector.undo_last(), None);
+    }
+
+    #[test]
+    fn undo_last_with_unapplier_uses_inverse() {
+        let mut projector =
+            ReversibleProjector::from_applier(&test_applier).with_unapplier(&test_unapplier);
+        projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-1"),
+        });
+
+        assert_eq!(
+            projector.undo_last(),
+            Some(TestEntity {
+                id: Some(String::from("id-1")),
+                timestamp: None,
+            })
+        );
+    }
+
+    #[test]
+    fn rewind_to_and_apply_truncates_redo_tail() {
+        let mut projector = ReversibleProjector::from_applier(&test_applier);
+        projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-1"),
+        });
+        projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-8"),
+        });
+        projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-3"),
+        });
+
+        let rewound = projector.rewind_to(1);
+        assert_eq!(
+            rewound,
+            TestEntity {
+                id: Some(String::from("id-1")),
+                timestamp: Some(String::from("ts-1")),
+            }
+        );
+
+        let state = projector.apply(TestEvent {
+            id: String::from("id-1"),
+            timestamp: String::from("ts-2"),
+        });
+        assert_eq!(
+            state,
+            TestEntity {
+                id: Some(String::from("id-1")),
+                timestamp: Some(String::from("ts-2")),
+            }
+        );
+        assert_eq!(projector.rewind_to(usize::MAX), state);
+    }
+
+    #[test]
+    fn stream_by_key_keeps_aggregates_separate() {
+        let events = vec![
+            TestEvent {
+                id: String::from("id-1"),
+                timestamp: String::from("ts-1"),
+            },
+            TestEvent {
+                id: String::from("id-2"),
+                timestamp: String::from("ts-5"),
+            },
+            TestEvent {
+                id: String::from("id-1"),
+                timestamp: String::from("ts-3"),
+            },
+        ];
+        let result: Vec<(String, TestEntity)> = Projector::from_applier(&test_applier)
+            .stream_entities_by_key(events.into_iter(), |event| event.id.clone())
+            .collect();
+
+        assert_eq!(
+            result,
+            vec![
+                (
+                    String::from("id-1"),
+                    TestEntity {
+                        id: Some(String::from("id-1")),
+                        timestamp: Some(String::from("ts-1")),
+                    }
+                ),
+                (
+                    String::from("id-2"),
+                    TestEntity {
+                        id: Some(String::from("id-2")),
+                        timestamp: Some(String::from("ts-5")),
+                    }
+                ),
+                (
+                    String::from("id-1"),
+                    TestEntity {
+                        id: Some(String::from("id-1")),
+                        timestamp: Some(String::from("ts-3")),
+                    }
+                ),
+            ],
+        );
+    }
+
+    #[test]
+    fn project_final_states_by_key() {
+        let events = vec![
+            TestEvent {
+                id: String::from("id-1"),
+                timestamp: String::from("ts-1"),
+            },
+            TestEvent {
+                id: String::from("id-2"),
+                timestamp: String::from("ts-5"),
+            },
+            TestEvent {
+                id: String::from("id-1"),
+                timestamp: String::from("ts-3"),
+            },
+        ];
+        let result = Projector::from_applier(&test_applier)
+            .project_final_states_by_key(events.into_iter(), |event| event.id.clone());
+
+        assert_eq!(
+            result.get("id-1"),
+            Some(&TestEntity {
+                id: Some(String::from("id-1")),
+                timestamp: Some(String::from("ts-3")),
+            })
+        );
+        assert_eq!(
+            result.get("id-2"),
+            Some(&TestEntity {
+                id: Some(String::from("id-2")),
+                timestamp: Some(String::from("ts-5")),
+            })
+        );
+    }
 }
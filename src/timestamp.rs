@@ -0,0 +1,338 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// An absolute instant, stored as whole seconds since the Unix epoch.
+///
+/// Unlike the raw ISO-8601 strings `encounter.rs` used to carry around,
+/// `Timestamp` compares chronologically regardless of the timezone offset
+/// or precision the original input was written in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
+pub struct Timestamp(i64);
+
+impl Timestamp {
+    /// Parses human or machine input into an absolute instant.
+    ///
+    /// The input is trimmed, then a leading `+` or `in ` is stripped. If
+    /// what remains is a bare integer `n`, it is read as a minute offset
+    /// from now. Otherwise a relative/natural-language expression is tried
+    /// (`yesterday 17:20`, `-1d`, `in 2 weeks`), falling back to an
+    /// absolute ISO-8601 datetime. Anything that resolves at or before the
+    /// Unix epoch is rejected, since this crate never deals in events from
+    /// before it existed.
+    pub fn parse(input: &str) -> Option<Timestamp> {
+        let trimmed = input.trim();
+        let unprefixed = trimmed
+            .strip_prefix('+')
+            .or_else(|| trimmed.strip_prefix("in "))
+            .unwrap_or(trimmed)
+            .trim();
+
+        let resolved = if let Ok(minutes) = unprefixed.parse::<i64>() {
+            now_secs() + minutes * 60
+        } else if let Some(secs) = parse_relative(unprefixed) {
+            secs
+        } else {
+            parse_absolute(trimmed)?
+        };
+
+        if resolved <= 0 {
+            eprintln!(
+                "Timestamp::parse: \"{}\" resolved to {} seconds since the epoch, rejecting",
+                input, resolved
+            );
+            return None;
+        }
+
+        Some(Timestamp(resolved))
+    }
+
+    /// Builds a `Timestamp` directly from seconds since the Unix epoch,
+    /// applying the same at-or-before-epoch rejection as `parse`.
+    pub fn from_epoch_seconds(seconds: i64) -> Option<Timestamp> {
+        if seconds <= 0 {
+            None
+        } else {
+            Some(Timestamp(seconds))
+        }
+    }
+
+    pub fn epoch_seconds(&self) -> i64 {
+        self.0
+    }
+
+    /// Formats as an extended ISO-8601 UTC datetime, e.g.
+    /// `2021-01-01T10:00:00Z` — the form `Timestamp::parse`'s absolute
+    /// fallback understands.
+    pub fn to_rfc3339(&self) -> String {
+        let days = self.0.div_euclid(86400);
+        let secs_of_day = self.0.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            (secs_of_day / 60) % 60,
+            secs_of_day % 60
+        )
+    }
+
+    /// Formats as the "basic" iCalendar UTC datetime form, e.g.
+    /// `20210101T100000Z`.
+    pub fn to_ics_basic(&self) -> String {
+        let days = self.0.div_euclid(86400);
+        let secs_of_day = self.0.rem_euclid(86400);
+        let (year, month, day) = civil_from_days(days);
+        format!(
+            "{:04}{:02}{:02}T{:02}{:02}{:02}Z",
+            year,
+            month,
+            day,
+            secs_of_day / 3600,
+            (secs_of_day / 60) % 60,
+            secs_of_day % 60
+        )
+    }
+
+    /// Parses the "basic" iCalendar UTC datetime form, e.g.
+    /// `20210101T100000Z`.
+    pub fn parse_ics_basic(input: &str) -> Option<Timestamp> {
+        let input = input.trim();
+        if input.len() < 15 || input.as_bytes()[8] != b'T' {
+            return None;
+        }
+        let year: i64 = input.get(0..4)?.parse().ok()?;
+        let month: u32 = input.get(4..6)?.parse().ok()?;
+        let day: u32 = input.get(6..8)?.parse().ok()?;
+        let hour: i64 = input.get(9..11)?.parse().ok()?;
+        let minute: i64 = input.get(11..13)?.parse().ok()?;
+        let second: i64 = input.get(13..15)?.parse().ok()?;
+
+        let days = days_from_civil(year, month, day)?;
+        Timestamp::from_epoch_seconds(days * 86400 + hour * 3600 + minute * 60 + second)
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn parse_relative(input: &str) -> Option<i64> {
+    let lower = input.to_lowercase();
+    let now = now_secs();
+
+    if let Some(rest) = lower.strip_prefix("yesterday") {
+        return Some(start_of_day(now, -1) + parse_time_of_day(rest.trim())?);
+    }
+    if let Some(rest) = lower.strip_prefix("tomorrow") {
+        return Some(start_of_day(now, 1) + parse_time_of_day(rest.trim())?);
+    }
+    if let Some(rest) = lower.strip_prefix("today") {
+        return Some(start_of_day(now, 0) + parse_time_of_day(rest.trim())?);
+    }
+
+    parse_signed_duration(&lower).map(|delta| now + delta)
+}
+
+fn start_of_day(now: i64, day_offset: i64) -> i64 {
+    let today_midnight = now.div_euclid(86400) * 86400;
+    today_midnight + day_offset * 86400
+}
+
+fn parse_time_of_day(input: &str) -> Option<i64> {
+    if input.is_empty() {
+        return Some(0);
+    }
+    let mut parts = input.splitn(2, ':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next().unwrap_or("0").parse().ok()?;
+    Some(hours * 3600 + minutes * 60)
+}
+
+fn parse_signed_duration(input: &str) -> Option<i64> {
+    let input = input.trim();
+    let (sign, rest) = match input.strip_prefix('-') {
+        Some(rest) => (-1, rest),
+        None => (1, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let rest = rest.trim();
+
+    let split_at = rest.find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len());
+    let (amount, unit) = rest.split_at(split_at);
+    let amount: i64 = amount.parse().ok()?;
+    let unit = unit.trim().trim_end_matches('s');
+
+    let seconds_per_unit = match unit {
+        "second" | "sec" => 1,
+        "minute" | "min" => 60,
+        "hour" | "h" => 3600,
+        "day" | "d" => 86400,
+        "week" | "w" => 86400 * 7,
+        _ => return None,
+    };
+
+    Some(sign * amount * seconds_per_unit)
+}
+
+/// Parses an ISO-8601 `YYYY-MM-DDThh:mm:ss` datetime, with optional
+/// fractional seconds and a `Z` or `±hh:mm` offset, into epoch seconds.
+fn parse_absolute(input: &str) -> Option<i64> {
+    if input.len() < 19 {
+        return None;
+    }
+    let bytes = input.as_bytes();
+    if bytes[4] != b'-' || bytes[7] != b'-' || (bytes[10] != b'T' && bytes[10] != b' ') {
+        return None;
+    }
+    if bytes[13] != b':' || bytes[16] != b':' {
+        return None;
+    }
+
+    let year: i64 = input.get(0..4)?.parse().ok()?;
+    let month: u32 = input.get(5..7)?.parse().ok()?;
+    let day: u32 = input.get(8..10)?.parse().ok()?;
+    let hour: i64 = input.get(11..13)?.parse().ok()?;
+    let minute: i64 = input.get(14..16)?.parse().ok()?;
+    let second: i64 = input.get(17..19)?.parse().ok()?;
+
+    let mut rest = &input[19..];
+    if let Some(stripped) = rest.strip_prefix('.') {
+        let digits = stripped
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(stripped.len());
+        rest = &stripped[digits..];
+    }
+
+    let offset_seconds = if rest.is_empty() || rest == "Z" {
+        0
+    } else {
+        let sign = match rest.as_bytes().first()? {
+            b'+' => 1,
+            b'-' => -1,
+            _ => return None,
+        };
+        let hours: i64 = rest.get(1..3)?.parse().ok()?;
+        let minutes: i64 = if rest.len() >= 6 {
+            rest.get(4..6)?.parse().ok()?
+        } else {
+            0
+        };
+        sign * (hours * 3600 + minutes * 60)
+    };
+
+    let days = days_from_civil(year, month, day)?;
+    Some(days * 86400 + hour * 3600 + minute * 60 + second - offset_seconds)
+}
+
+/// Howard Hinnant's `days_from_civil`: maps a Gregorian date to the number
+/// of days since the Unix epoch, correct across the whole proleptic
+/// Gregorian calendar without pulling in a date library.
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let mp = (month as i64 + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146097 + doe - 719468)
+}
+
+/// Howard Hinnant's `civil_from_days`: the inverse of `days_from_civil`,
+/// mapping a day count since the Unix epoch back to a Gregorian date.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = if month <= 2 { y + 1 } else { y };
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_absolute_zulu() {
+        let ts = Timestamp::parse("2021-01-01T00:00:00.000Z").unwrap();
+        assert_eq!(ts, Timestamp(1609459200));
+    }
+
+    #[test]
+    fn parses_absolute_with_offset() {
+        let zulu = Timestamp::parse("2021-01-01T10:00:00Z").unwrap();
+        let plus_two = Timestamp::parse("2021-01-01T12:00:00+02:00").unwrap();
+        assert_eq!(zulu, plus_two);
+    }
+
+    #[test]
+    fn chronological_ordering_survives_mixed_offsets() {
+        let earlier = Timestamp::parse("2021-01-01T23:30:00+02:00").unwrap();
+        let later = Timestamp::parse("2021-01-01T22:00:00Z").unwrap();
+        assert!(earlier < later);
+    }
+
+    #[test]
+    fn parses_minute_offset() {
+        let now = now_secs();
+        let ts = Timestamp::parse("+15").unwrap();
+        assert_eq!(ts.0, now + 15 * 60);
+    }
+
+    #[test]
+    fn parses_relative_day_with_time() {
+        let now = now_secs();
+        let ts = Timestamp::parse("yesterday 17:20").unwrap();
+        assert_eq!(ts.0, start_of_day(now, -1) + 17 * 3600 + 20 * 60);
+    }
+
+    #[test]
+    fn parses_signed_duration_shorthand() {
+        let now = now_secs();
+        let ts = Timestamp::parse("-1d").unwrap();
+        assert_eq!(ts.0, now - 86400);
+    }
+
+    #[test]
+    fn parses_in_n_weeks() {
+        let now = now_secs();
+        let ts = Timestamp::parse("in 2 weeks").unwrap();
+        assert_eq!(ts.0, now + 2 * 7 * 86400);
+    }
+
+    #[test]
+    fn rejects_epoch_or_before() {
+        assert_eq!(Timestamp::parse("1970-01-01T00:00:00Z"), None);
+        assert_eq!(Timestamp::parse("1960-01-01T00:00:00Z"), None);
+    }
+
+    #[test]
+    fn rejects_garbage() {
+        assert_eq!(Timestamp::parse("not a date"), None);
+    }
+
+    #[test]
+    fn ics_basic_round_trips() {
+        let ts = Timestamp::parse("2021-01-01T10:30:05Z").unwrap();
+        assert_eq!(ts.to_ics_basic(), "20210101T103005Z");
+        assert_eq!(Timestamp::parse_ics_basic("20210101T103005Z"), Some(ts));
+    }
+
+    #[test]
+    fn rfc3339_round_trips_through_parse() {
+        let ts = Timestamp::parse("2021-01-01T10:30:05Z").unwrap();
+        assert_eq!(ts.to_rfc3339(), "2021-01-01T10:30:05Z");
+        assert_eq!(Timestamp::parse(&ts.to_rfc3339()), Some(ts));
+    }
+}
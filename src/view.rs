@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+
+type Column<'a, TEntity> = (&'a str, Box<dyn Fn(&TEntity) -> String + 'a>);
+
+/// A reusable presentation layer over a stream of projected entities,
+/// borrowing the "add/remove property column" and "sort by property"
+/// interactions from the task UI without pulling any of that back into
+/// `Projector` itself.
+///
+/// Columns are registered once as named accessors; callers then pick a
+/// subset/order of them to render, or ask for a stable multi-key sort.
+pub struct View<'a, TEntity> {
+    columns: Vec<Column<'a, TEntity>>,
+}
+
+impl<'a, TEntity> View<'a, TEntity> {
+    pub fn new() -> Self {
+        View { columns: Vec::new() }
+    }
+
+    /// Registers a named column. Later columns with the same name replace
+    /// earlier ones.
+    pub fn column(mut self, name: &'a str, accessor: impl Fn(&TEntity) -> String + 'a) -> Self {
+        self.columns.retain(|(existing, _)| *existing != name);
+        self.columns.push((name, Box::new(accessor)));
+        self
+    }
+
+    fn accessor(&self, name: &str) -> Option<&(dyn Fn(&TEntity) -> String + 'a)> {
+        self.columns
+            .iter()
+            .find(|(existing, _)| *existing == name)
+            .map(|(_, accessor)| accessor.as_ref())
+    }
+
+    /// Renders `entities` as rows using only the named/ordered subset of
+    /// registered columns. Unknown column names are silently skipped.
+    pub fn table(
+        &self,
+        entities: impl Iterator<Item = TEntity>,
+        column_names: &[&str],
+    ) -> Vec<Vec<String>> {
+        let accessors: Vec<&(dyn Fn(&TEntity) -> String + 'a)> = column_names
+            .iter()
+            .filter_map(|name| self.accessor(name))
+            .collect();
+
+        entities
+            .map(|entity| accessors.iter().map(|accessor| accessor(&entity)).collect())
+            .collect()
+    }
+
+    /// Materializes `entities` and stably sorts them by `column_names`, in
+    /// order (so the first name is the primary key), then renders the
+    /// result using those same columns.
+    pub fn sort_by(
+        &self,
+        entities: impl Iterator<Item = TEntity>,
+        column_names: &[&str],
+    ) -> Vec<Vec<String>> {
+        let accessors: Vec<&(dyn Fn(&TEntity) -> String + 'a)> = column_names
+            .iter()
+            .filter_map(|name| self.accessor(name))
+            .collect();
+
+        let mut materialized: Vec<TEntity> = entities.collect();
+        materialized.sort_by(|a, b| {
+            accessors
+                .iter()
+                .map(|accessor| accessor(a).cmp(&accessor(b)))
+                .find(|ord| *ord != Ordering::Equal)
+                .unwrap_or(Ordering::Equal)
+        });
+
+        self.table(materialized.into_iter(), column_names)
+    }
+}
+
+impl<'a, TEntity> Default for View<'a, TEntity> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encounter::Participant;
+    use crate::timestamp::Timestamp;
+
+    fn participant(id: &str, start: &str) -> Participant {
+        Participant {
+            id: id.to_owned(),
+            start: Timestamp::parse(start),
+            end: None,
+        }
+    }
+
+    fn participant_view<'a>() -> View<'a, Participant> {
+        View::new()
+            .column("id", |p: &Participant| p.id.clone())
+            .column("start", |p: &Participant| {
+                p.start.map(|ts| ts.to_rfc3339()).unwrap_or_default()
+            })
+    }
+
+    #[test]
+    fn table_renders_requested_columns_in_order() {
+        let participants = vec![participant("a", "2021-01-01T00:00:00Z")];
+        let table = participant_view().table(participants.into_iter(), &["start", "id"]);
+        assert_eq!(table, vec![vec!["2021-01-01T00:00:00Z".to_owned(), "a".to_owned()]]);
+    }
+
+    #[test]
+    fn sort_by_orders_participants_by_start_then_id() {
+        let participants = vec![
+            participant("b", "2021-01-01T12:00:00Z"),
+            participant("a", "2021-01-01T00:00:00Z"),
+            participant("c", "2021-01-01T00:00:00Z"),
+        ];
+
+        let table = participant_view().sort_by(participants.into_iter(), &["start", "id"]);
+
+        assert_eq!(
+            table,
+            vec![
+                vec!["2021-01-01T00:00:00Z".to_owned(), "a".to_owned()],
+                vec!["2021-01-01T00:00:00Z".to_owned(), "c".to_owned()],
+                vec!["2021-01-01T12:00:00Z".to_owned(), "b".to_owned()],
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_columns_are_skipped() {
+        let participants = vec![participant("a", "2021-01-01T00:00:00Z")];
+        let table = participant_view().table(participants.into_iter(), &["missing", "id"]);
+        assert_eq!(table, vec![vec!["a".to_owned()]]);
+    }
+}